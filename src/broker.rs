@@ -1,9 +1,11 @@
-use druid::{im::Vector, ExtEventSink, Target};
+use std::time::Duration;
+
+use druid::{Data, ExtEventSink, Target};
 use tokio::sync::mpsc;
 
 use crate::{
     core::{
-        config::Contact,
+        config::{Contact, RelayConfig},
         conversations::ConvsNotifications,
         core::{CoreTaskHandle, CoreTaskHandleEvent},
     },
@@ -19,8 +21,15 @@ use crate::{
     delegate::BROKER_NOTI,
 };
 
+const FLUSH_INTERVAL: Duration = Duration::from_millis(500);
+
 pub enum BrokerEvent {
-    AddRelay { url: String },
+    AddRelay {
+        url: String,
+        proxy: Option<String>,
+        read: bool,
+        write: bool,
+    },
     RemoveRelay { url: String },
     ConnectRelay { url: String },
     DisconnectRelay { url: String },
@@ -32,18 +41,62 @@ pub enum BrokerEvent {
     SetConversation { pk: String },
     SendMessage { pk: String, content: String },
     LoadConfigs,
+    DismissNotification { id: String },
+    MarkAllRead,
+    PublishContactList,
+    Flush,
 }
 
 pub enum BrokerNotification {
     ConfigUpdated { config: ConfigState },
 }
 
+#[derive(Clone, Data)]
+pub enum Notification {
+    NewMessageFrom {
+        id: String,
+        pk: String,
+        preview: String,
+    },
+    RelayConnected {
+        id: String,
+        url: String,
+    },
+    RelayDisconnected {
+        id: String,
+        url: String,
+    },
+    ContactAdded {
+        id: String,
+        pk: String,
+    },
+    PublishFailed {
+        id: String,
+        url: String,
+    },
+}
+
+impl Notification {
+    pub fn id(&self) -> &str {
+        match self {
+            Notification::NewMessageFrom { id, .. }
+            | Notification::RelayConnected { id, .. }
+            | Notification::RelayDisconnected { id, .. }
+            | Notification::ContactAdded { id, .. }
+            | Notification::PublishFailed { id, .. } => id,
+        }
+    }
+}
+
 pub async fn start_broker(
     event_sink: ExtEventSink,
     mut broker_receiver: mpsc::Receiver<BrokerEvent>,
 ) {
     let mut core_handle = CoreTaskHandle::new();
 
+    //Hydrate relays, contacts and conversation history from the local store
+    core_handle.restore_from_store().await;
+
     //Load configs
     send_res_ev_to_druid(
         &event_sink,
@@ -59,13 +112,27 @@ pub async fn start_broker(
         while let Ok(noti) = rec_convs_noti.recv().await {
             match noti {
                 ConvsNotifications::NewMessage(new_msg) => {
+                    let msg_pk = new_msg.pk.clone();
+                    let notification = Notification::NewMessageFrom {
+                        id: format!("msg-{}", new_msg.id),
+                        pk: msg_pk.clone(),
+                        preview: new_msg.content.clone(),
+                    };
                     ev_sink_clone.add_idle_callback(move |data: &mut AppState| {
-                        if data.selected_conv.is_some() {
+                        let is_selected_conv = data
+                            .selected_conv
+                            .as_ref()
+                            .map(|conv| conv.pk == msg_pk)
+                            .unwrap_or(false);
+
+                        if is_selected_conv {
                             let mut updated_conv = data.selected_conv.clone().unwrap();
                             updated_conv
                                 .messages
                                 .push_back(MessageState::from_entity(new_msg));
                             data.selected_conv = Some(updated_conv);
+                        } else {
+                            data.notifications.push_back(notification);
                         }
                     });
                 }
@@ -73,10 +140,51 @@ pub async fn start_broker(
         }
     });
 
-    while let Some(broker_event) = broker_receiver.recv().await {
+    let mut rec_config_noti = core_handle.get_config_notifications();
+    let ev_sink_clone = event_sink.clone();
+
+    tokio::spawn(async move {
+        while let Ok((relays, contacts)) = rec_config_noti.recv().await {
+            let updated_config_state = build_config_state(&relays, &contacts);
+            ev_sink_clone.add_idle_callback(move |data: &mut AppState| {
+                data.config = updated_config_state;
+            });
+        }
+    });
+
+    let mut rec_publish_noti = core_handle.get_publish_notifications();
+    let ev_sink_clone = event_sink.clone();
+
+    tokio::spawn(async move {
+        while let Ok(failed_relay_url) = rec_publish_noti.recv().await {
+            let notification = Notification::PublishFailed {
+                id: format!("publish-failed-{}", failed_relay_url),
+                url: failed_relay_url,
+            };
+            ev_sink_clone.add_idle_callback(move |data: &mut AppState| {
+                data.notifications.push_back(notification);
+            });
+        }
+    });
+
+    let mut ticker = tokio::time::interval(FLUSH_INTERVAL);
+
+    loop {
+        let broker_event = tokio::select! {
+            _ = ticker.tick() => {
+                core_handle.flush().await;
+                core_handle.pump_incoming();
+                continue;
+            }
+            event = broker_receiver.recv() => match event {
+                Some(event) => event,
+                None => break,
+            },
+        };
+
         match broker_event {
             BrokerEvent::SendMessage { pk, content } => {
-                core_handle.send_msg_to_contact(&pk, &content).await;
+                core_handle.queue_msg_to_contact(&pk, &content).await;
             }
             BrokerEvent::SetConversation { pk } => {
                 if let Some(conv) = core_handle.get_conv(pk) {
@@ -95,8 +203,15 @@ pub async fn start_broker(
                 core_handle.subscribe().await;
                 update_user_state(&event_sink, &core_handle);
             }
-            BrokerEvent::AddRelay { url } => {
-                if let CoreTaskHandleEvent::RelayAdded(Ok(_)) = core_handle.add_relay(url).await {
+            BrokerEvent::AddRelay {
+                url,
+                proxy,
+                read,
+                write,
+            } => {
+                if let CoreTaskHandleEvent::RelayAdded(Ok(_)) =
+                    core_handle.add_relay(url, proxy, read, write).await
+                {
                     update_config_state(&event_sink, &core_handle).await;
                 }
             }
@@ -108,18 +223,44 @@ pub async fn start_broker(
                 }
             }
             BrokerEvent::ConnectRelay { url } => {
-                core_handle.connect_relay(url).await;
+                if core_handle.connect_relay(url.clone()).await.is_ok() {
+                    event_sink.add_idle_callback(move |data: &mut AppState| {
+                        data.notifications.push_back(Notification::RelayConnected {
+                            id: format!("relay-connected-{url}"),
+                            url,
+                        });
+                    });
+                }
             }
             BrokerEvent::DisconnectRelay { url } => {
-                core_handle.disconnect_relay(url).await;
+                if core_handle.disconnect_relay(url.clone()).await.is_ok() {
+                    event_sink.add_idle_callback(move |data: &mut AppState| {
+                        data.notifications
+                            .push_back(Notification::RelayDisconnected {
+                                id: format!("relay-disconnected-{url}"),
+                                url,
+                            });
+                    });
+                }
             }
 
-            BrokerEvent::SubscribeInRelays { pk } => {
+            BrokerEvent::SubscribeInRelays { pk: _ } => {
                 core_handle.subscribe().await;
+                update_config_state(&event_sink, &core_handle).await;
             }
             BrokerEvent::AddContact { new_contact } => {
-                let res = core_handle.add_contact(new_contact).await;
-                update_config_state(&event_sink, &core_handle).await;
+                let pk = new_contact.pk.to_string();
+                if core_handle.add_contact(new_contact).await.is_ok() {
+                    core_handle.mark_contact_request_sent(&pk);
+                    let _ = core_handle.publish_contact_list().await;
+                    update_config_state(&event_sink, &core_handle).await;
+                    event_sink.add_idle_callback(move |data: &mut AppState| {
+                        data.notifications.push_back(Notification::ContactAdded {
+                            id: format!("contact-added-{}", pk),
+                            pk,
+                        });
+                    });
+                }
             }
             BrokerEvent::RemoveContact { contact } => {
                 let res = core_handle.remove_contact(contact).await;
@@ -128,17 +269,37 @@ pub async fn start_broker(
             BrokerEvent::LoadConfigs => {
                 update_config_state(&event_sink, &core_handle).await;
             }
+            BrokerEvent::DismissNotification { id } => {
+                event_sink.add_idle_callback(move |data: &mut AppState| {
+                    data.notifications.retain(|n| n.id() != id);
+                });
+            }
+            BrokerEvent::MarkAllRead => {
+                event_sink.add_idle_callback(move |data: &mut AppState| {
+                    data.notifications.clear();
+                });
+            }
+            BrokerEvent::PublishContactList => {
+                let _ = core_handle.publish_contact_list().await;
+            }
+            BrokerEvent::Flush => {
+                core_handle.flush().await;
+            }
         }
     }
 }
 
 fn load_config(core: &CoreTaskHandle) -> ConfigState {
-    let (relays_url, contacts) = core.get_config();
+    let (relays, contacts) = core.get_config();
+    build_config_state(&relays, &contacts)
+}
+
+fn build_config_state(relays: &[RelayConfig], contacts: &[Contact]) -> ConfigState {
     let mut updated_config_state = ConfigState::new();
-    updated_config_state.relays_url = Vector::from(relays_url);
+    updated_config_state.relays_url = relays.iter().map(|r| r.url.clone()).collect();
     updated_config_state.contacts = contacts
         .iter()
-        .map(|c| ContactState::new(&c.alias, &c.pk.to_string()))
+        .map(|c| ContactState::new(&c.alias, &c.pk.to_string(), &c.request_status))
         .collect();
 
     updated_config_state
@@ -155,13 +316,8 @@ fn update_user_state(event_sink: &ExtEventSink, core_handle: &CoreTaskHandle) {
 }
 
 async fn update_config_state(event_sink: &ExtEventSink, core_handle: &CoreTaskHandle) {
-    let (relays_url, contacts) = core_handle.get_config();
-    let mut updated_config_state = ConfigState::new();
-    updated_config_state.relays_url = Vector::from(relays_url);
-    updated_config_state.contacts = contacts
-        .iter()
-        .map(|c| ContactState::new(&c.alias, &c.pk.to_string()))
-        .collect();
+    let (relays, contacts) = core_handle.get_config();
+    let updated_config_state = build_config_state(&relays, &contacts);
     event_sink.add_idle_callback(move |data: &mut AppState| {
         data.config = updated_config_state;
     });
@@ -0,0 +1,47 @@
+use std::net::SocketAddr;
+
+use serde::{Deserialize, Serialize};
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub enum ContactRequestStatus {
+    None,
+    RequestSent,
+    RequestReceived,
+    RequestAccepted,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Contact {
+    pub alias: String,
+    pub pk: String,
+    pub request_status: ContactRequestStatus,
+}
+
+impl Contact {
+    pub fn new(alias: &str, pk: &str) -> Self {
+        Contact {
+            alias: alias.to_owned(),
+            pk: pk.to_owned(),
+            request_status: ContactRequestStatus::None,
+        }
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct RelayConfig {
+    pub url: String,
+    pub proxy: Option<SocketAddr>,
+    pub read: bool,
+    pub write: bool,
+}
+
+impl RelayConfig {
+    pub fn new(url: String, proxy: Option<SocketAddr>, read: bool, write: bool) -> Self {
+        RelayConfig {
+            url,
+            proxy,
+            read,
+            write,
+        }
+    }
+}
@@ -0,0 +1,21 @@
+use serde::{Deserialize, Serialize};
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct MessageEntity {
+    pub id: String,
+    pub pk: String,
+    pub content: String,
+    pub created_at: i64,
+    pub outgoing: bool,
+}
+
+#[derive(Debug, Clone)]
+pub struct Conversation {
+    pub pk: String,
+    pub messages: Vec<MessageEntity>,
+}
+
+#[derive(Debug, Clone)]
+pub enum ConvsNotifications {
+    NewMessage(MessageEntity),
+}
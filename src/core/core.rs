@@ -0,0 +1,432 @@
+use std::collections::HashMap;
+use std::net::SocketAddr;
+
+use tokio::sync::{broadcast, mpsc};
+
+use super::{
+    config::{Contact, ContactRequestStatus, RelayConfig},
+    conversations::{Conversation, ConvsNotifications, MessageEntity},
+    dialer,
+    nostr::{NostrEvent, KIND_CONTACT_LIST, KIND_DM},
+    reader::{self, IncomingEvent, ReaderMessage},
+    store::Store,
+    user::User,
+    writer::{self, WriterMessage},
+};
+
+const STORE_PATH: &str = "./data/loquaz.sled";
+
+pub enum CoreTaskHandleEvent {
+    RelayAdded(Result<(), String>),
+    RemovedRelay(Result<(), String>),
+}
+
+pub struct CoreTaskHandle {
+    user: User,
+    relays: Vec<RelayConfig>,
+    contacts: Vec<Contact>,
+    conversations: HashMap<String, Conversation>,
+    convs_sender: broadcast::Sender<ConvsNotifications>,
+    config_sender: broadcast::Sender<(Vec<RelayConfig>, Vec<Contact>)>,
+    publish_failure_sender: broadcast::Sender<String>,
+    writer_sender: mpsc::Sender<WriterMessage>,
+    reader_sender: Option<mpsc::Sender<ReaderMessage>>,
+    incoming_receiver: Option<mpsc::Receiver<IncomingEvent>>,
+    store: Store,
+}
+
+impl CoreTaskHandle {
+    pub fn new() -> Self {
+        let (convs_sender, _) = broadcast::channel(128);
+        let (config_sender, _) = broadcast::channel(32);
+        let (publish_failure_sender, _) = broadcast::channel(32);
+        let writer_sender = writer::spawn_writer(publish_failure_sender.clone());
+        CoreTaskHandle {
+            user: User::new(),
+            relays: Vec::new(),
+            contacts: Vec::new(),
+            conversations: HashMap::new(),
+            convs_sender,
+            config_sender,
+            publish_failure_sender,
+            writer_sender,
+            reader_sender: None,
+            incoming_receiver: None,
+            store: Store::open(STORE_PATH),
+        }
+    }
+
+    /// Lazily spawns the relay-subscription task once the user's pubkey is
+    /// known (it's needed to filter incoming DMs and contact-list "p" tags).
+    fn ensure_reader(&mut self) {
+        if self.reader_sender.is_some() {
+            return;
+        }
+        if let Some(pk) = self.user.get_pk_opt() {
+            let (sender, receiver) = reader::spawn_reader(pk);
+            self.reader_sender = Some(sender);
+            self.incoming_receiver = Some(receiver);
+        }
+    }
+
+    /// Drains whatever incoming messages/contact-list events the reader
+    /// task has collected since the last call, applying them to local
+    /// state. Called periodically from the broker's main loop.
+    pub fn pump_incoming(&mut self) {
+        let Some(receiver) = self.incoming_receiver.as_mut() else {
+            return;
+        };
+        let mut drained = Vec::new();
+        while let Ok(incoming) = receiver.try_recv() {
+            drained.push(incoming);
+        }
+        for incoming in drained {
+            match incoming {
+                IncomingEvent::Message { relay_url, message } => {
+                    let created_at = message.created_at;
+                    self.ingest_message(message);
+                    self.record_relay_event(&relay_url, created_at);
+                }
+                IncomingEvent::ContactList {
+                    from_pk, lists_us, ..
+                } => self.observe_contact_list(&from_pk, lists_us),
+            }
+        }
+    }
+
+    /// Emits the current relays/contacts to anyone listening for config
+    /// changes that happen outside of a direct `add_relay`/`add_contact`
+    /// call, e.g. a contact's request status flipping after a publish.
+    fn publish_config_update(&self) {
+        let _ = self
+            .config_sender
+            .send((self.relays.clone(), self.contacts.clone()));
+    }
+
+    pub fn get_config_notifications(
+        &self,
+    ) -> broadcast::Receiver<(Vec<RelayConfig>, Vec<Contact>)> {
+        self.config_sender.subscribe()
+    }
+
+    pub fn get_publish_notifications(&self) -> broadcast::Receiver<String> {
+        self.publish_failure_sender.subscribe()
+    }
+
+    /// Hydrates relays, contacts and every persisted conversation from the
+    /// local sled store, so history survives a restart.
+    pub async fn restore_from_store(&mut self) {
+        self.relays = self.store.load_relays();
+        self.contacts = self.store.load_contacts();
+
+        for relay in self.relays.clone() {
+            let _ = self.writer_sender.send(WriterMessage::NewRelay(relay)).await;
+        }
+
+        for pk in self.store.known_conversation_pks() {
+            let messages = self.store.load_messages(&pk);
+            self.conversations.insert(
+                pk.clone(),
+                Conversation {
+                    pk,
+                    messages,
+                },
+            );
+        }
+    }
+
+    pub fn get_convs_notifications(&self) -> broadcast::Receiver<ConvsNotifications> {
+        self.convs_sender.subscribe()
+    }
+
+    pub fn get_config(&self) -> (Vec<RelayConfig>, Vec<Contact>) {
+        (self.relays.clone(), self.contacts.clone())
+    }
+
+    pub async fn add_relay(
+        &mut self,
+        url: String,
+        proxy: Option<String>,
+        read: bool,
+        write: bool,
+    ) -> CoreTaskHandleEvent {
+        let proxy_addr = match proxy.as_deref().map(str::parse::<SocketAddr>) {
+            Some(Ok(addr)) => Some(addr),
+            Some(Err(_)) => {
+                return CoreTaskHandleEvent::RelayAdded(Err(format!(
+                    "invalid SOCKS5 proxy address for relay {url}"
+                )))
+            }
+            None => None,
+        };
+
+        let relay = RelayConfig::new(url, proxy_addr, read, write);
+        self.relays.push(relay.clone());
+        self.store.save_relays(&self.relays);
+        let _ = self.writer_sender.send(WriterMessage::NewRelay(relay)).await;
+        CoreTaskHandleEvent::RelayAdded(Ok(()))
+    }
+
+    pub async fn remove_relay(&mut self, url: String) -> CoreTaskHandleEvent {
+        let before = self.relays.len();
+        self.relays.retain(|r| r.url != url);
+        if self.relays.len() == before {
+            return CoreTaskHandleEvent::RemovedRelay(Err(format!("no such relay: {url}")));
+        }
+        self.store.save_relays(&self.relays);
+        // write=false deregisters the relay as a writer fan-out target.
+        let _ = self
+            .writer_sender
+            .send(WriterMessage::NewRelay(RelayConfig::new(
+                url, None, false, false,
+            )))
+            .await;
+        CoreTaskHandleEvent::RemovedRelay(Ok(()))
+    }
+
+    pub async fn connect_relay(&mut self, url: String) -> Result<(), String> {
+        let relay = self
+            .relays
+            .iter()
+            .find(|r| r.url == url)
+            .cloned()
+            .ok_or_else(|| format!("no such relay: {url}"))?;
+        dialer::dial(&relay)
+            .await
+            .map(|_| ())
+            .map_err(|e| e.to_string())
+    }
+
+    pub async fn disconnect_relay(&mut self, url: String) -> Result<(), String> {
+        if self.relays.iter().any(|r| r.url == url) {
+            Ok(())
+        } else {
+            Err(format!("no such relay: {url}"))
+        }
+    }
+
+    /// Persists the message locally, then hands a kind-4 DM event to the
+    /// writer task for a coalesced send to every write-enabled relay on the
+    /// next flush; read-only mirrors are never used as a publish target.
+    pub async fn queue_msg_to_contact(&mut self, pk: &str, content: &str) {
+        let id = new_event_id();
+        let created_at = now();
+        let msg = MessageEntity {
+            id: id.clone(),
+            pk: pk.to_owned(),
+            content: content.to_owned(),
+            created_at,
+            outgoing: true,
+        };
+        let event = NostrEvent::new(
+            id,
+            self.user.get_pk_opt().unwrap_or_default(),
+            created_at,
+            KIND_DM,
+            vec![vec!["p".to_owned(), pk.to_owned()]],
+            content.to_owned(),
+        );
+        self.ingest_message(msg);
+        let _ = self.writer_sender.send(WriterMessage::Publish(event)).await;
+    }
+
+    /// Registers every read-enabled relay with the reader task, each with
+    /// its own last-seen marker so the `REQ` only asks for what's new.
+    pub async fn subscribe(&mut self) {
+        self.ensure_reader();
+        let Some(reader_sender) = self.reader_sender.clone() else {
+            return;
+        };
+        let read_relays: Vec<RelayConfig> =
+            self.relays.iter().filter(|r| r.read).cloned().collect();
+        for relay in read_relays {
+            let since = self.store.get_relay_last_seen(&relay.url).unwrap_or(0);
+            let _ = reader_sender.send(ReaderMessage::NewRelay { relay, since }).await;
+        }
+    }
+
+    /// The write-through point for every incoming message: deduplicates by
+    /// event id, persists to the local store, updates the in-memory
+    /// conversation and notifies subscribers. Outgoing messages from
+    /// `queue_msg_to_contact` go through the same path.
+    fn ingest_message(&mut self, msg: MessageEntity) {
+        if !self.store.insert_message(&msg) {
+            return;
+        }
+
+        self.conversations
+            .entry(msg.pk.clone())
+            .or_insert_with(|| Conversation {
+                pk: msg.pk.clone(),
+                messages: Vec::new(),
+            })
+            .messages
+            .push(msg.clone());
+
+        let _ = self.convs_sender.send(ConvsNotifications::NewMessage(msg));
+    }
+
+    /// Called for each message a relay delivers so its last-seen marker
+    /// advances and the next `subscribe` only asks for what's new.
+    pub fn record_relay_event(&mut self, relay_url: &str, created_at: i64) {
+        self.store.set_relay_last_seen(relay_url, created_at);
+    }
+
+    pub fn get_user(&self) -> User {
+        self.user.clone()
+    }
+
+    pub fn import_user_sk(&mut self, sk: String) {
+        self.user = User::from_sk(&sk);
+    }
+
+    pub fn gen_new_user_keypair(&mut self) {
+        self.user = User::generate();
+    }
+
+    pub fn get_conv(&self, pk: String) -> Option<Conversation> {
+        self.conversations.get(&pk).cloned()
+    }
+
+    pub async fn add_contact(&mut self, new_contact: Contact) -> Result<(), String> {
+        self.contacts.push(new_contact);
+        self.store.save_contacts(&self.contacts);
+        Ok(())
+    }
+
+    pub async fn remove_contact(&mut self, contact: Contact) -> Result<(), String> {
+        self.contacts.retain(|c| c.pk != contact.pk);
+        self.store.save_contacts(&self.contacts);
+        Ok(())
+    }
+
+    /// Queues a kind-3 contact-list event, tagging every known contact
+    /// pubkey, for the writer task to send to every write-enabled relay on
+    /// the next flush.
+    pub async fn publish_contact_list(&mut self) -> Result<(), String> {
+        if !self.relays.iter().any(|r| r.write) {
+            return Err("no write relays configured".to_owned());
+        }
+
+        let tags = self
+            .contacts
+            .iter()
+            .map(|c| vec!["p".to_owned(), c.pk.clone()])
+            .collect();
+        let event = NostrEvent::new(
+            new_event_id(),
+            self.user.get_pk_opt().unwrap_or_default(),
+            now(),
+            KIND_CONTACT_LIST,
+            tags,
+            String::new(),
+        );
+        let _ = self.writer_sender.send(WriterMessage::Publish(event)).await;
+        Ok(())
+    }
+
+    /// Queues an immediate drain of whatever is pending in the writer task,
+    /// on top of its periodic flush.
+    pub async fn flush(&self) {
+        let _ = self.writer_sender.send(WriterMessage::Flush).await;
+    }
+
+    /// Marks a contact as having an outgoing contact-request from us (our
+    /// kind-3 list naming them), so the UI can show it as pending instead
+    /// of accepted. The status is set whether or not that list is published.
+    pub fn mark_contact_request_sent(&mut self, pk: &str) {
+        if let Some(contact) = self.contacts.iter_mut().find(|c| c.pk == pk) {
+            contact.request_status = ContactRequestStatus::RequestSent;
+        }
+        self.store.save_contacts(&self.contacts);
+        self.publish_config_update();
+    }
+
+    /// Applied to an incoming kind-3 event observed by the reader task:
+    /// once a known contact's own list names us, the follow is mutual and
+    /// their status flips to accepted. A list that does not name us leaves
+    /// the status unchanged.
+    fn observe_contact_list(&mut self, from_pk: &str, lists_us: bool) {
+        if !lists_us {
+            return;
+        }
+        if let Some(contact) = self.contacts.iter_mut().find(|c| c.pk == from_pk) {
+            contact.request_status = ContactRequestStatus::RequestAccepted;
+            self.store.save_contacts(&self.contacts);
+            self.publish_config_update();
+        }
+    }
+}
+
+fn new_event_id() -> String {
+    use std::sync::atomic::{AtomicU64, Ordering};
+    use std::time::{SystemTime, UNIX_EPOCH};
+    static COUNTER: AtomicU64 = AtomicU64::new(0);
+    let nanos = SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .unwrap_or_default()
+        .as_nanos();
+    format!("{:x}-{:x}", nanos, COUNTER.fetch_add(1, Ordering::Relaxed))
+}
+
+fn now() -> i64 {
+    use std::time::{SystemTime, UNIX_EPOCH};
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .unwrap_or_default()
+        .as_secs() as i64
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn test_handle() -> CoreTaskHandle {
+        let (convs_sender, _) = broadcast::channel(128);
+        let (config_sender, _) = broadcast::channel(32);
+        let (publish_failure_sender, _) = broadcast::channel(32);
+        let writer_sender = writer::spawn_writer(publish_failure_sender.clone());
+        let path = std::env::temp_dir().join(format!(
+            "loquaz-core-test-{:?}",
+            std::thread::current().id()
+        ));
+        let _ = std::fs::remove_dir_all(&path);
+        CoreTaskHandle {
+            user: User::new(),
+            relays: Vec::new(),
+            contacts: Vec::new(),
+            conversations: HashMap::new(),
+            convs_sender,
+            config_sender,
+            publish_failure_sender,
+            writer_sender,
+            reader_sender: None,
+            incoming_receiver: None,
+            store: Store::open(path),
+        }
+    }
+
+    /// A contact looking at a different conversation should still see the
+    /// new message land in their persisted history and get a notification,
+    /// whether the message is outgoing or (as here) a received DM.
+    #[tokio::test]
+    async fn incoming_message_is_persisted_and_notified() {
+        let mut handle = test_handle();
+        let mut notifications = handle.get_convs_notifications();
+
+        handle.ingest_message(MessageEntity {
+            id: "evt-1".to_owned(),
+            pk: "contact-pk".to_owned(),
+            content: "hey".to_owned(),
+            created_at: 1,
+            outgoing: false,
+        });
+
+        let received = notifications.try_recv().expect("no notification");
+        let ConvsNotifications::NewMessage(msg) = received;
+        assert_eq!(msg.pk, "contact-pk");
+        assert!(!msg.outgoing);
+        let conv = handle.get_conv("contact-pk".to_owned()).unwrap();
+        assert_eq!(conv.messages.len(), 1);
+    }
+}
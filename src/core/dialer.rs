@@ -0,0 +1,122 @@
+use std::io;
+use std::net::SocketAddr;
+
+use tokio::io::{AsyncReadExt, AsyncWriteExt};
+use tokio::net::TcpStream;
+
+use super::config::RelayConfig;
+
+/// Open a TCP connection to a relay, routing through its configured SOCKS5
+/// proxy (e.g. Tor) when one is set, so `.onion` relays never get dialed
+/// directly over clearnet.
+pub async fn dial(relay: &RelayConfig) -> io::Result<TcpStream> {
+    let (host, port) = host_port(&relay.url)?;
+    match relay.proxy {
+        Some(proxy_addr) => dial_via_socks5(proxy_addr, &host, port).await,
+        None => TcpStream::connect((host.as_str(), port)).await,
+    }
+}
+
+fn host_port(url: &str) -> io::Result<(String, u16)> {
+    let without_scheme = url
+        .trim_start_matches("wss://")
+        .trim_start_matches("ws://");
+    let host_port = without_scheme.split('/').next().unwrap_or(without_scheme);
+    match host_port.rsplit_once(':') {
+        Some((host, port)) => {
+            let port = port.parse::<u16>().map_err(|_| {
+                io::Error::new(io::ErrorKind::InvalidInput, "invalid relay port")
+            })?;
+            Ok((host.to_owned(), port))
+        }
+        None => Ok((host_port.to_owned(), 443)),
+    }
+}
+
+/// Minimal SOCKS5 CONNECT handshake (RFC 1928) with no authentication,
+/// enough to reach Tor's local SOCKS5 listener.
+async fn dial_via_socks5(proxy_addr: SocketAddr, host: &str, port: u16) -> io::Result<TcpStream> {
+    let mut stream = TcpStream::connect(proxy_addr).await?;
+
+    // Greeting: version 5, one method, "no auth".
+    stream.write_all(&[0x05, 0x01, 0x00]).await?;
+    let mut greeting_reply = [0u8; 2];
+    stream.read_exact(&mut greeting_reply).await?;
+    if greeting_reply != [0x05, 0x00] {
+        return Err(io::Error::new(
+            io::ErrorKind::Other,
+            "SOCKS5 proxy rejected the no-auth handshake",
+        ));
+    }
+
+    // CONNECT request using a domain name so `.onion` addresses resolve on
+    // the proxy side rather than leaking to the local resolver.
+    let mut request = vec![0x05, 0x01, 0x00, 0x03, host.len() as u8];
+    request.extend_from_slice(host.as_bytes());
+    request.extend_from_slice(&port.to_be_bytes());
+    stream.write_all(&request).await?;
+
+    let mut reply_header = [0u8; 4];
+    stream.read_exact(&mut reply_header).await?;
+    if reply_header[1] != 0x00 {
+        return Err(io::Error::new(
+            io::ErrorKind::Other,
+            format!("SOCKS5 CONNECT failed with status {}", reply_header[1]),
+        ));
+    }
+
+    // Drain the bound address in the reply before handing the stream back.
+    let addr_len = match reply_header[3] {
+        0x01 => 4,
+        0x04 => 16,
+        0x03 => {
+            let mut len_byte = [0u8; 1];
+            stream.read_exact(&mut len_byte).await?;
+            len_byte[0] as usize
+        }
+        other => {
+            return Err(io::Error::new(
+                io::ErrorKind::Other,
+                format!("unsupported SOCKS5 address type {other}"),
+            ))
+        }
+    };
+    let mut rest = vec![0u8; addr_len + 2];
+    stream.read_exact(&mut rest).await?;
+
+    Ok(stream)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn host_port_splits_explicit_port() {
+        assert_eq!(
+            host_port("wss://relay.example.com:4848").unwrap(),
+            ("relay.example.com".to_owned(), 4848)
+        );
+    }
+
+    #[test]
+    fn host_port_defaults_to_443() {
+        assert_eq!(
+            host_port("wss://relay.example.com").unwrap(),
+            ("relay.example.com".to_owned(), 443)
+        );
+    }
+
+    #[test]
+    fn host_port_strips_path_and_ws_scheme() {
+        assert_eq!(
+            host_port("ws://relay.example.com:80/nostr").unwrap(),
+            ("relay.example.com".to_owned(), 80)
+        );
+    }
+
+    #[test]
+    fn host_port_rejects_non_numeric_port() {
+        assert!(host_port("wss://relay.example.com:notaport").is_err());
+    }
+}
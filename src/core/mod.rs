@@ -0,0 +1,9 @@
+pub mod config;
+pub mod conversations;
+pub mod core;
+pub mod dialer;
+pub mod nostr;
+pub mod reader;
+pub mod store;
+pub mod user;
+pub mod writer;
@@ -0,0 +1,124 @@
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+
+pub const KIND_CONTACT_LIST: u32 = 3;
+pub const KIND_DM: u32 = 4;
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct NostrEvent {
+    pub id: String,
+    pub pubkey: String,
+    pub created_at: i64,
+    pub kind: u32,
+    pub tags: Vec<Vec<String>>,
+    pub content: String,
+    pub sig: String,
+}
+
+impl NostrEvent {
+    pub fn new(
+        id: String,
+        pubkey: String,
+        created_at: i64,
+        kind: u32,
+        tags: Vec<Vec<String>>,
+        content: String,
+    ) -> Self {
+        NostrEvent {
+            id,
+            pubkey,
+            created_at,
+            kind,
+            tags,
+            content,
+            sig: String::new(),
+        }
+    }
+
+    pub fn recipient(&self) -> Option<&str> {
+        self.tags
+            .iter()
+            .find(|tag| tag.first().map(String::as_str) == Some("p"))
+            .and_then(|tag| tag.get(1))
+            .map(String::as_str)
+    }
+}
+
+pub fn encode_event_frame(event: &NostrEvent) -> String {
+    serde_json::json!(["EVENT", event]).to_string()
+}
+
+pub fn encode_req_frame(sub_id: &str, since: i64) -> String {
+    serde_json::json!(["REQ", sub_id, {"since": since}]).to_string()
+}
+
+pub enum RelayMessage {
+    Event(NostrEvent),
+    Ok { id: String, accepted: bool },
+}
+
+/// Parses a single relay response line per NIP-01: `["EVENT", sub_id, event]`
+/// or `["OK", event_id, accepted, message]`. Anything else (EOSE, NOTICE,
+/// malformed input) is ignored.
+pub fn decode_relay_message(line: &str) -> Option<RelayMessage> {
+    let frame: Vec<Value> = serde_json::from_str(line).ok()?;
+    match frame.first()?.as_str()? {
+        "EVENT" => {
+            let event_value = frame.get(2)?;
+            let event: NostrEvent = serde_json::from_value(event_value.clone()).ok()?;
+            Some(RelayMessage::Event(event))
+        }
+        "OK" => Some(RelayMessage::Ok {
+            id: frame.get(1)?.as_str()?.to_owned(),
+            accepted: frame.get(2)?.as_bool()?,
+        }),
+        _ => None,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn recipient_reads_the_p_tag() {
+        let event = NostrEvent::new(
+            "id".into(),
+            "pk".into(),
+            0,
+            KIND_DM,
+            vec![vec!["p".into(), "recipient-pk".into()]],
+            "hi".into(),
+        );
+        assert_eq!(event.recipient(), Some("recipient-pk"));
+    }
+
+    #[test]
+    fn decode_relay_message_parses_ok_frame() {
+        let line = r#"["OK","abc123",true,""]"#;
+        match decode_relay_message(line) {
+            Some(RelayMessage::Ok { id, accepted }) => {
+                assert_eq!(id, "abc123");
+                assert!(accepted);
+            }
+            _ => panic!("expected an OK frame"),
+        }
+    }
+
+    #[test]
+    fn decode_relay_message_parses_event_frame() {
+        let event = NostrEvent::new(
+            "id".into(),
+            "pk".into(),
+            1,
+            KIND_DM,
+            vec![],
+            "hi".into(),
+        );
+        let line = serde_json::json!(["EVENT", "sub0", event]).to_string();
+        match decode_relay_message(&line) {
+            Some(RelayMessage::Event(decoded)) => assert_eq!(decoded.id, "id"),
+            _ => panic!("expected an EVENT frame"),
+        }
+    }
+}
@@ -0,0 +1,138 @@
+use std::collections::HashSet;
+use std::time::Duration;
+
+use tokio::io::{AsyncBufReadExt, AsyncWriteExt, BufReader};
+use tokio::sync::mpsc;
+use tokio::time::timeout;
+
+use super::config::RelayConfig;
+use super::conversations::MessageEntity;
+use super::dialer;
+use super::nostr::{self, NostrEvent, RelayMessage, KIND_CONTACT_LIST, KIND_DM};
+
+const READ_TIMEOUT: Duration = Duration::from_secs(5);
+const RECONNECT_DELAY: Duration = Duration::from_secs(10);
+
+pub enum ReaderMessage {
+    NewRelay { relay: RelayConfig, since: i64 },
+}
+
+pub enum IncomingEvent {
+    Message {
+        relay_url: String,
+        message: MessageEntity,
+    },
+    ContactList {
+        relay_url: String,
+        from_pk: String,
+        lists_us: bool,
+    },
+}
+
+/// Spawns the relay-subscription task. Each read-enabled relay gets its own
+/// long-lived connect/REQ/read loop so one slow or dead relay never blocks
+/// events arriving from the others; a dropped connection is retried rather
+/// than given up on.
+pub fn spawn_reader(
+    own_pk: String,
+) -> (mpsc::Sender<ReaderMessage>, mpsc::Receiver<IncomingEvent>) {
+    let (control_sender, mut control_receiver) = mpsc::channel::<ReaderMessage>(32);
+    let (event_sender, event_receiver) = mpsc::channel::<IncomingEvent>(256);
+
+    tokio::spawn(async move {
+        let mut subscribed: HashSet<String> = HashSet::new();
+
+        while let Some(ReaderMessage::NewRelay { relay, since }) = control_receiver.recv().await {
+            if !relay.read || !subscribed.insert(relay.url.clone()) {
+                continue;
+            }
+            let own_pk = own_pk.clone();
+            let event_sender = event_sender.clone();
+            tokio::spawn(async move {
+                run_relay_subscription(relay, since, own_pk, event_sender).await;
+            });
+        }
+    });
+
+    (control_sender, event_receiver)
+}
+
+async fn run_relay_subscription(
+    relay: RelayConfig,
+    since: i64,
+    own_pk: String,
+    event_sender: mpsc::Sender<IncomingEvent>,
+) {
+    loop {
+        let _ = subscribe_once(&relay, since, &own_pk, &event_sender).await;
+        tokio::time::sleep(RECONNECT_DELAY).await;
+    }
+}
+
+/// Dials the relay, sends a single `REQ` for events since the last-seen
+/// marker, then streams `EVENT` frames until the connection drops.
+async fn subscribe_once(
+    relay: &RelayConfig,
+    since: i64,
+    own_pk: &str,
+    event_sender: &mpsc::Sender<IncomingEvent>,
+) -> std::io::Result<()> {
+    let mut stream = dialer::dial(relay).await?;
+    let req = nostr::encode_req_frame(&format!("sub-{}", relay.url), since);
+    stream.write_all(req.as_bytes()).await?;
+    stream.write_all(b"\n").await?;
+
+    let mut reader = BufReader::new(stream);
+    loop {
+        let mut line = String::new();
+        let read = match timeout(READ_TIMEOUT, reader.read_line(&mut line)).await {
+            Ok(result) => result?,
+            Err(_) => continue,
+        };
+        if read == 0 {
+            return Ok(()); // relay closed the stream; the caller reconnects.
+        }
+        if let Some(RelayMessage::Event(event)) = nostr::decode_relay_message(line.trim()) {
+            route_event(relay, own_pk, event, event_sender).await;
+        }
+    }
+}
+
+async fn route_event(
+    relay: &RelayConfig,
+    own_pk: &str,
+    event: NostrEvent,
+    event_sender: &mpsc::Sender<IncomingEvent>,
+) {
+    match event.kind {
+        KIND_DM if event.pubkey != own_pk && event.recipient() == Some(own_pk) => {
+            let message = MessageEntity {
+                id: event.id,
+                pk: event.pubkey,
+                content: event.content,
+                created_at: event.created_at,
+                outgoing: false,
+            };
+            let _ = event_sender
+                .send(IncomingEvent::Message {
+                    relay_url: relay.url.clone(),
+                    message,
+                })
+                .await;
+        }
+        KIND_CONTACT_LIST => {
+            let lists_us = event.tags.iter().any(|tag| {
+                tag.first().map(String::as_str) == Some("p")
+                    && tag.get(1).map(String::as_str) == Some(own_pk)
+            });
+            let _ = event_sender
+                .send(IncomingEvent::ContactList {
+                    relay_url: relay.url.clone(),
+                    from_pk: event.pubkey,
+                    lists_us,
+                })
+                .await;
+        }
+        _ => {}
+    }
+}
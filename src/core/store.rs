@@ -0,0 +1,189 @@
+use std::path::Path;
+
+use serde::{Deserialize, Serialize};
+use sled::Db;
+
+use super::config::{Contact, RelayConfig};
+use super::conversations::MessageEntity;
+
+const RELAYS_KEY: &str = "relays";
+const CONTACTS_KEY: &str = "contacts";
+const RELAY_LAST_SEEN_TREE: &str = "relay_last_seen";
+const MESSAGES_TREE_PREFIX: &str = "messages:";
+
+#[derive(Serialize, Deserialize)]
+struct StoredRelayConfig {
+    url: String,
+    proxy: Option<String>,
+    read: bool,
+    write: bool,
+}
+
+/// Embedded, pure-Rust persistence for decrypted DM history, the relay
+/// list and contacts, so conversations survive an app restart.
+pub struct Store {
+    db: Db,
+}
+
+impl Store {
+    pub fn open<P: AsRef<Path>>(path: P) -> Self {
+        let db = sled::open(path).expect("failed to open the local sled store");
+        Store { db }
+    }
+
+    fn messages_tree(&self, pk: &str) -> sled::Tree {
+        self.db
+            .open_tree(format!("{MESSAGES_TREE_PREFIX}{pk}"))
+            .expect("failed to open a per-contact message tree")
+    }
+
+    /// Persists a message keyed by its relay event id, so replays of the
+    /// same event from multiple relays never create a duplicate entry.
+    /// Returns `false` when the event id was already on disk.
+    pub fn insert_message(&self, msg: &MessageEntity) -> bool {
+        let tree = self.messages_tree(&msg.pk);
+        if tree.contains_key(msg.id.as_bytes()).unwrap_or(false) {
+            return false;
+        }
+        let value = serde_json::to_vec(msg).expect("failed to serialize message");
+        tree.insert(msg.id.as_bytes(), value)
+            .expect("failed to persist message");
+        true
+    }
+
+    /// Loads a contact's full decrypted history, ordered oldest first.
+    pub fn load_messages(&self, pk: &str) -> Vec<MessageEntity> {
+        let mut messages: Vec<MessageEntity> = self
+            .messages_tree(pk)
+            .iter()
+            .values()
+            .filter_map(Result::ok)
+            .filter_map(|value| serde_json::from_slice(&value).ok())
+            .collect();
+        messages.sort_by_key(|msg| msg.created_at);
+        messages
+    }
+
+    /// Every contact pubkey that has at least one persisted message, used
+    /// to hydrate conversations on startup without knowing the contact
+    /// list up front.
+    pub fn known_conversation_pks(&self) -> Vec<String> {
+        self.db
+            .tree_names()
+            .into_iter()
+            .filter_map(|name| String::from_utf8(name.to_vec()).ok())
+            .filter_map(|name| name.strip_prefix(MESSAGES_TREE_PREFIX).map(str::to_owned))
+            .collect()
+    }
+
+    pub fn save_relays(&self, relays: &[RelayConfig]) {
+        let stored: Vec<StoredRelayConfig> = relays
+            .iter()
+            .map(|relay| StoredRelayConfig {
+                url: relay.url.clone(),
+                proxy: relay.proxy.map(|addr| addr.to_string()),
+                read: relay.read,
+                write: relay.write,
+            })
+            .collect();
+        let value = serde_json::to_vec(&stored).expect("failed to serialize relays");
+        self.db
+            .insert(RELAYS_KEY, value)
+            .expect("failed to persist relays");
+    }
+
+    pub fn load_relays(&self) -> Vec<RelayConfig> {
+        self.db
+            .get(RELAYS_KEY)
+            .ok()
+            .flatten()
+            .and_then(|value| serde_json::from_slice::<Vec<StoredRelayConfig>>(&value).ok())
+            .unwrap_or_default()
+            .into_iter()
+            .map(|relay| {
+                RelayConfig::new(
+                    relay.url,
+                    relay.proxy.and_then(|addr| addr.parse().ok()),
+                    relay.read,
+                    relay.write,
+                )
+            })
+            .collect()
+    }
+
+    pub fn save_contacts(&self, contacts: &[Contact]) {
+        let value = serde_json::to_vec(contacts).expect("failed to serialize contacts");
+        self.db
+            .insert(CONTACTS_KEY, value)
+            .expect("failed to persist contacts");
+    }
+
+    pub fn load_contacts(&self) -> Vec<Contact> {
+        self.db
+            .get(CONTACTS_KEY)
+            .ok()
+            .flatten()
+            .and_then(|value| serde_json::from_slice(&value).ok())
+            .unwrap_or_default()
+    }
+
+    /// The timestamp of the newest event seen on a relay, so `subscribe`
+    /// can ask for events since that point instead of refetching history.
+    pub fn set_relay_last_seen(&self, url: &str, created_at: i64) {
+        let tree = self
+            .db
+            .open_tree(RELAY_LAST_SEEN_TREE)
+            .expect("failed to open relay_last_seen tree");
+        let newest = self
+            .get_relay_last_seen(url)
+            .map_or(created_at, |seen| seen.max(created_at));
+        tree.insert(url.as_bytes(), &newest.to_be_bytes())
+            .expect("failed to persist relay last-seen marker");
+    }
+
+    pub fn get_relay_last_seen(&self, url: &str) -> Option<i64> {
+        let tree = self.db.open_tree(RELAY_LAST_SEEN_TREE).ok()?;
+        let bytes = tree.get(url.as_bytes()).ok()??;
+        Some(i64::from_be_bytes(bytes.as_ref().try_into().ok()?))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn open_temp_store() -> Store {
+        let path = std::env::temp_dir().join(format!(
+            "loquaz-store-test-{:?}",
+            std::thread::current().id()
+        ));
+        let _ = std::fs::remove_dir_all(&path);
+        Store::open(path)
+    }
+
+    fn message(id: &str) -> MessageEntity {
+        MessageEntity {
+            id: id.to_owned(),
+            pk: "contact-pk".to_owned(),
+            content: "hi".to_owned(),
+            created_at: 1,
+            outgoing: false,
+        }
+    }
+
+    #[test]
+    fn insert_message_rejects_a_duplicate_id() {
+        let store = open_temp_store();
+        assert!(store.insert_message(&message("evt-1")));
+        assert!(!store.insert_message(&message("evt-1")));
+        assert_eq!(store.load_messages("contact-pk").len(), 1);
+    }
+
+    #[test]
+    fn insert_message_keeps_distinct_ids() {
+        let store = open_temp_store();
+        assert!(store.insert_message(&message("evt-1")));
+        assert!(store.insert_message(&message("evt-2")));
+        assert_eq!(store.load_messages("contact-pk").len(), 2);
+    }
+}
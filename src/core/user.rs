@@ -0,0 +1,57 @@
+#[derive(Debug, Clone)]
+pub struct User {
+    sk: Option<String>,
+    pk: Option<String>,
+}
+
+impl User {
+    pub fn new() -> Self {
+        User { sk: None, pk: None }
+    }
+
+    pub fn from_sk(sk: &str) -> Self {
+        User {
+            sk: Some(sk.to_owned()),
+            pk: Some(derive_pk(sk)),
+        }
+    }
+
+    pub fn generate() -> Self {
+        let sk = format!("{:032x}{:032x}", rand_u64(), rand_u64());
+        let pk = derive_pk(&sk);
+        User {
+            sk: Some(sk),
+            pk: Some(pk),
+        }
+    }
+
+    pub fn get_sk(&self) -> Option<String> {
+        self.sk.clone()
+    }
+
+    pub fn get_pk(&self) -> String {
+        self.pk.clone().expect("user has no key pair yet")
+    }
+
+    pub fn get_pk_opt(&self) -> Option<String> {
+        self.pk.clone()
+    }
+}
+
+fn derive_pk(sk: &str) -> String {
+    // Placeholder key derivation until the real secp256k1 signing scheme is wired in.
+    format!("pub{}", sk)
+}
+
+/// Reads 8 bytes from the OS CSPRNG rather than seeding from the clock,
+/// since this value ends up in a signing key.
+fn rand_u64() -> u64 {
+    use std::fs::File;
+    use std::io::Read;
+
+    let mut buf = [0u8; 8];
+    File::open("/dev/urandom")
+        .and_then(|mut f| f.read_exact(&mut buf))
+        .expect("failed to read entropy from /dev/urandom");
+    u64::from_le_bytes(buf)
+}
@@ -0,0 +1,123 @@
+use std::io;
+use std::time::Duration;
+
+use tokio::io::{AsyncBufReadExt, AsyncWriteExt, BufReader};
+use tokio::sync::{broadcast, mpsc};
+use tokio::time::timeout;
+
+use super::config::RelayConfig;
+use super::dialer;
+use super::nostr::{self, NostrEvent, RelayMessage};
+
+/// Messages accepted by the dedicated writer task spawned by
+/// `CoreTaskHandle`. Keeping this off the main broker loop means a slow
+/// relay during a flush never stalls `SendMessage`/`AddRelay` handling.
+pub enum WriterMessage {
+    /// Registers (or, when `write` is false, deregisters) a relay as a
+    /// fan-out target for future flushes.
+    NewRelay(RelayConfig),
+    /// Queues an event to be sent on the next flush.
+    Publish(NostrEvent),
+    /// Drains whatever is queued, writing it to every known write relay
+    /// in parallel.
+    Flush,
+}
+
+const MAX_ATTEMPTS: u32 = 3;
+const RESPONSE_TIMEOUT: Duration = Duration::from_secs(5);
+
+/// Spawns the writer task and returns the channel used to talk to it.
+/// Publish failures that survive every retry are reported on
+/// `failure_sender` so the broker can surface a `PublishFailed`
+/// notification.
+pub fn spawn_writer(failure_sender: broadcast::Sender<String>) -> mpsc::Sender<WriterMessage> {
+    let (sender, mut receiver) = mpsc::channel::<WriterMessage>(256);
+
+    tokio::spawn(async move {
+        let mut write_relays: Vec<RelayConfig> = Vec::new();
+        let mut pending: Vec<NostrEvent> = Vec::new();
+
+        while let Some(message) = receiver.recv().await {
+            match message {
+                WriterMessage::NewRelay(relay) => {
+                    write_relays.retain(|r| r.url != relay.url);
+                    if relay.write {
+                        write_relays.push(relay);
+                    }
+                }
+                WriterMessage::Publish(event) => {
+                    pending.push(event);
+                }
+                WriterMessage::Flush => {
+                    if pending.is_empty() || write_relays.is_empty() {
+                        continue;
+                    }
+                    let batch = std::mem::take(&mut pending);
+                    let sends = write_relays.clone().into_iter().map(|relay| {
+                        let batch = batch.clone();
+                        let failure_sender = failure_sender.clone();
+                        tokio::spawn(async move {
+                            send_batch(&relay, &batch, &failure_sender).await
+                        })
+                    });
+                    for send in sends.collect::<Vec<_>>() {
+                        let _ = send.await;
+                    }
+                }
+            }
+        }
+    });
+
+    sender
+}
+
+/// Sends every event in the batch to a single relay, trying the whole
+/// batch up to `MAX_ATTEMPTS` times in all before reporting the relay as
+/// failed.
+async fn send_batch(
+    relay: &RelayConfig,
+    batch: &[NostrEvent],
+    failure_sender: &broadcast::Sender<String>,
+) {
+    for attempt in 1..=MAX_ATTEMPTS {
+        match try_send_batch(relay, batch).await {
+            Ok(()) => return,
+            Err(_) if attempt == MAX_ATTEMPTS => {
+                let _ = failure_sender.send(relay.url.clone());
+            }
+            Err(_) => continue,
+        }
+    }
+}
+
+/// Dials the relay and writes each event as an `["EVENT", ...]` frame,
+/// waiting for the relay's `OK` response before moving on to the next one.
+/// The batch is considered failed as soon as a relay rejects or fails to
+/// acknowledge any event in it.
+async fn try_send_batch(relay: &RelayConfig, batch: &[NostrEvent]) -> io::Result<()> {
+    let stream = dialer::dial(relay).await?;
+    let mut reader = BufReader::new(stream);
+
+    for event in batch {
+        let frame = nostr::encode_event_frame(event);
+        reader.get_mut().write_all(frame.as_bytes()).await?;
+        reader.get_mut().write_all(b"\n").await?;
+
+        let mut line = String::new();
+        timeout(RESPONSE_TIMEOUT, reader.read_line(&mut line))
+            .await
+            .map_err(|_| io::Error::new(io::ErrorKind::TimedOut, "relay did not acknowledge"))??;
+
+        match nostr::decode_relay_message(line.trim()) {
+            Some(RelayMessage::Ok { accepted, .. }) if accepted => continue,
+            _ => {
+                return Err(io::Error::other(format!(
+                    "relay {} rejected event {}",
+                    relay.url, event.id
+                )))
+            }
+        }
+    }
+
+    Ok(())
+}
@@ -0,0 +1,26 @@
+use druid::{im::Vector, Data, Lens};
+
+use crate::broker::Notification;
+
+use super::state::{
+    config_state::ConfigState, conversation_state::ConversationState, user_state::UserState,
+};
+
+#[derive(Clone, Data, Lens)]
+pub struct AppState {
+    pub user: UserState,
+    pub config: ConfigState,
+    pub selected_conv: Option<ConversationState>,
+    pub notifications: Vector<Notification>,
+}
+
+impl AppState {
+    pub fn new() -> Self {
+        AppState {
+            user: UserState::new("", ""),
+            config: ConfigState::new(),
+            selected_conv: None,
+            notifications: Vector::new(),
+        }
+    }
+}
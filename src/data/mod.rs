@@ -0,0 +1,2 @@
+pub mod app_state;
+pub mod state;
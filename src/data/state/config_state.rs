@@ -0,0 +1,18 @@
+use druid::{im::Vector, Data, Lens};
+
+use super::contact_state::ContactState;
+
+#[derive(Clone, Data, Lens)]
+pub struct ConfigState {
+    pub relays_url: Vector<String>,
+    pub contacts: Vector<ContactState>,
+}
+
+impl ConfigState {
+    pub fn new() -> Self {
+        ConfigState {
+            relays_url: Vector::new(),
+            contacts: Vector::new(),
+        }
+    }
+}
@@ -0,0 +1,39 @@
+use druid::Data;
+
+use crate::core::config::ContactRequestStatus as CoreContactRequestStatus;
+
+#[derive(Clone, Data, PartialEq)]
+pub enum ContactRequestStatus {
+    None,
+    RequestSent,
+    RequestReceived,
+    RequestAccepted,
+}
+
+impl From<&CoreContactRequestStatus> for ContactRequestStatus {
+    fn from(status: &CoreContactRequestStatus) -> Self {
+        match status {
+            CoreContactRequestStatus::None => ContactRequestStatus::None,
+            CoreContactRequestStatus::RequestSent => ContactRequestStatus::RequestSent,
+            CoreContactRequestStatus::RequestReceived => ContactRequestStatus::RequestReceived,
+            CoreContactRequestStatus::RequestAccepted => ContactRequestStatus::RequestAccepted,
+        }
+    }
+}
+
+#[derive(Clone, Data)]
+pub struct ContactState {
+    pub alias: String,
+    pub pk: String,
+    pub request_status: ContactRequestStatus,
+}
+
+impl ContactState {
+    pub fn new(alias: &str, pk: &str, request_status: &CoreContactRequestStatus) -> Self {
+        ContactState {
+            alias: alias.to_owned(),
+            pk: pk.to_owned(),
+            request_status: request_status.into(),
+        }
+    }
+}
@@ -0,0 +1,35 @@
+use druid::{im::Vector, Data, Lens};
+
+use crate::core::conversations::{Conversation, MessageEntity};
+
+#[derive(Clone, Data, Lens)]
+pub struct ConversationState {
+    pub pk: String,
+    pub messages: Vector<MessageState>,
+}
+
+impl ConversationState {
+    pub fn from_entity(conv: Conversation) -> Self {
+        ConversationState {
+            pk: conv.pk,
+            messages: conv.messages.into_iter().map(MessageState::from_entity).collect(),
+        }
+    }
+}
+
+#[derive(Clone, Data)]
+pub struct MessageState {
+    pub pk: String,
+    pub content: String,
+    pub outgoing: bool,
+}
+
+impl MessageState {
+    pub fn from_entity(msg: MessageEntity) -> Self {
+        MessageState {
+            pk: msg.pk,
+            content: msg.content,
+            outgoing: msg.outgoing,
+        }
+    }
+}
@@ -0,0 +1,4 @@
+pub mod config_state;
+pub mod contact_state;
+pub mod conversation_state;
+pub mod user_state;
@@ -0,0 +1,16 @@
+use druid::Data;
+
+#[derive(Clone, Data)]
+pub struct UserState {
+    pub sk: String,
+    pub pk: String,
+}
+
+impl UserState {
+    pub fn new(sk: &str, pk: &str) -> Self {
+        UserState {
+            sk: sk.to_owned(),
+            pk: pk.to_owned(),
+        }
+    }
+}
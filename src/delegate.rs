@@ -0,0 +1,29 @@
+use druid::{AppDelegate, Command, DelegateCtx, Env, Handled, Selector, Target};
+
+use crate::{broker::BrokerNotification, data::app_state::AppState};
+
+pub const BROKER_NOTI: Selector<BrokerNotification> = Selector::new("loquaz.broker-notification");
+
+pub struct Delegate;
+
+impl AppDelegate<AppState> for Delegate {
+    fn command(
+        &mut self,
+        _ctx: &mut DelegateCtx,
+        _target: Target,
+        cmd: &Command,
+        data: &mut AppState,
+        _env: &Env,
+    ) -> Handled {
+        if let Some(noti) = cmd.get(BROKER_NOTI) {
+            match noti {
+                BrokerNotification::ConfigUpdated { config } => {
+                    data.config = config.clone();
+                }
+            }
+            return Handled::Yes;
+        }
+
+        Handled::No
+    }
+}
@@ -0,0 +1,33 @@
+mod broker;
+mod core;
+mod data;
+mod delegate;
+
+use druid::{AppLauncher, Widget, WidgetExt, WindowDesc};
+use tokio::sync::mpsc;
+
+use crate::{broker::BrokerEvent, data::app_state::AppState, delegate::Delegate};
+
+fn main() {
+    let (broker_sender, broker_receiver) = mpsc::channel::<BrokerEvent>(128);
+
+    let main_window = WindowDesc::new(ui_builder()).title("loquaz");
+    let launcher = AppLauncher::with_window(main_window);
+
+    let event_sink = launcher.get_external_handle();
+    std::thread::spawn(move || {
+        let runtime = tokio::runtime::Runtime::new().expect("failed to start the async runtime");
+        runtime.block_on(broker::start_broker(event_sink, broker_receiver));
+    });
+
+    launcher
+        .delegate(Delegate)
+        .launch(AppState::new())
+        .expect("failed to launch loquaz");
+
+    drop(broker_sender);
+}
+
+fn ui_builder() -> impl Widget<AppState> {
+    druid::widget::Label::new("loquaz").center()
+}